@@ -1,7 +1,8 @@
 #![allow(dead_code)]
 
 use map_macro::map;
-use std::{collections::HashMap, ops::Deref, str::FromStr, vec};
+use std::{collections::HashMap, ops::Deref, str::FromStr, sync::Arc, vec};
+use warp::Filter;
 
 /*
  * Primitives
@@ -11,7 +12,10 @@ trait PrimitiveParse: Sized {
 }
 
 trait Parse: Sized {
-    fn parse(stream: &str) -> Result<(&str, Self), anyhow::Error>;
+    /// Parses a value starting at `start_line` (the value's line number
+    /// within the original document), returning the unparsed remainder of
+    /// `stream`, the line the remainder starts at, and the parsed value.
+    fn parse(stream: &str, start_line: u16) -> Result<(&str, u16, Self), anyhow::Error>;
 }
 
 #[derive(
@@ -26,6 +30,7 @@ trait Parse: Sized {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[serde(transparent)]
 struct Integer(u32);
 
 impl PrimitiveParse for Integer {
@@ -48,7 +53,22 @@ impl From<u32> for Integer {
     }
 }
 
+impl std::fmt::Display for Integer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Integer {
+    type Err = anyhow::Error;
+
+    fn from_str(stream: &str) -> Result<Self, Self::Err> {
+        Integer::from_stream(stream)
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
 struct Float(f32);
 
 impl PrimitiveParse for Float {
@@ -71,7 +91,22 @@ impl From<f32> for Float {
     }
 }
 
+impl std::fmt::Display for Float {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Float {
+    type Err = anyhow::Error;
+
+    fn from_str(stream: &str) -> Result<Self, Self::Err> {
+        Float::from_stream(stream)
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
 struct Number(f64);
 
 impl Deref for Number {
@@ -94,6 +129,20 @@ impl From<f64> for Number {
     }
 }
 
+impl std::fmt::Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Number {
+    type Err = anyhow::Error;
+
+    fn from_str(stream: &str) -> Result<Self, Self::Err> {
+        Number::from_stream(stream)
+    }
+}
+
 #[derive(
     Default,
     Debug,
@@ -106,6 +155,7 @@ impl From<f64> for Number {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[serde(transparent)]
 struct Text(String);
 
 impl Deref for Text {
@@ -123,8 +173,14 @@ impl PrimitiveParse for Text {
 }
 
 impl Parse for Text {
-    fn parse(_stream: &str) -> Result<(&str, Self), anyhow::Error> {
-        todo!()
+    fn parse(stream: &str, start_line: u16) -> Result<(&str, u16, Self), anyhow::Error> {
+        let tokens = tokenize(stream, start_line)?;
+        let line = tokens
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("expected a line of text"))?;
+        let (tail, next_line) = tail_after(stream, &tokens, 1, start_line);
+
+        Ok((tail, next_line, Text::from(line.content)))
     }
 }
 
@@ -140,6 +196,20 @@ impl From<&str> for Text {
     }
 }
 
+impl std::fmt::Display for Text {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Text {
+    type Err = anyhow::Error;
+
+    fn from_str(stream: &str) -> Result<Self, Self::Err> {
+        Text::from_stream(stream)
+    }
+}
+
 #[derive(
     Default,
     Debug,
@@ -152,6 +222,7 @@ impl From<&str> for Text {
     serde::Serialize,
     serde::Deserialize,
 )]
+#[serde(transparent)]
 struct Boolean(bool);
 
 impl Deref for Boolean {
@@ -174,7 +245,22 @@ impl From<bool> for Boolean {
     }
 }
 
+impl std::fmt::Display for Boolean {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Boolean {
+    type Err = anyhow::Error;
+
+    fn from_str(stream: &str) -> Result<Self, Self::Err> {
+        Boolean::from_stream(stream)
+    }
+}
+
 #[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
 struct DateTime(chrono::NaiveDateTime);
 
 impl DateTime {
@@ -206,7 +292,22 @@ impl From<chrono::NaiveDateTime> for DateTime {
     }
 }
 
+impl std::fmt::Display for DateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for DateTime {
+    type Err = anyhow::Error;
+
+    fn from_str(stream: &str) -> Result<Self, Self::Err> {
+        DateTime::from_stream(stream)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
 struct Uri(url::Url);
 
 impl Uri {
@@ -244,25 +345,154 @@ impl From<url::Url> for Uri {
     }
 }
 
-#[derive(Default, Debug, Clone, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+impl std::fmt::Display for Uri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl FromStr for Uri {
+    type Err = anyhow::Error;
+
+    fn from_str(stream: &str) -> Result<Self, Self::Err> {
+        Uri::from_stream(stream)
+    }
+}
+
+fn validate_coordinate(lat: f32, lon: f32) -> Result<(), anyhow::Error> {
+    if !(-90.0..=90.0).contains(&lat) {
+        return Err(anyhow::anyhow!("latitude {lat} is out of range (-90 to 90)"));
+    }
+
+    if !(-180.0..=180.0).contains(&lon) {
+        return Err(anyhow::anyhow!("longitude {lon} is out of range (-180 to 180)"));
+    }
+
+    Ok(())
+}
+
+#[derive(Default, Debug, Clone, PartialEq, PartialOrd)]
 struct GeoPoint(Float, Float);
 
 impl GeoPoint {
-    fn new(lat: impl Into<Float>, lon: impl Into<Float>) -> Self {
-        GeoPoint(lat.into(), lon.into())
+    fn new(lat: impl Into<Float>, lon: impl Into<Float>) -> Result<Self, anyhow::Error> {
+        let lat = lat.into();
+        let lon = lon.into();
+        validate_coordinate(lat.0, lon.0)?;
+        Ok(GeoPoint(lat, lon))
     }
 }
 
-#[derive(Default, Debug, Clone, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+impl serde::Serialize for GeoPoint {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("GeoPoint", 2)?;
+        state.serialize_field("type", "Point")?;
+        state.serialize_field("coordinates", &[self.1.0, self.0.0])?;
+        state.end()
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct GeoPointRaw {
+    r#type: String,
+    coordinates: [f32; 2],
+}
+
+impl<'de> serde::Deserialize<'de> for GeoPoint {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = GeoPointRaw::deserialize(deserializer)?;
+        if raw.r#type != "Point" {
+            return Err(serde::de::Error::custom(format!(
+                "expected GeoJSON type 'Point', got '{}'",
+                raw.r#type
+            )));
+        }
+
+        let [lon, lat] = raw.coordinates;
+        GeoPoint::new(lat, lon).map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, PartialOrd)]
 struct GeoPolygon(Vec<(Float, Float)>);
 
 impl GeoPolygon {
-    fn new(points: Vec<(Float, Float)>) -> Self {
-        GeoPolygon(points)
+    fn new(mut points: Vec<(Float, Float)>) -> Result<Self, anyhow::Error> {
+        if points.is_empty() {
+            return Err(anyhow::anyhow!("a polygon requires at least one point"));
+        }
+
+        for (lat, lon) in &points {
+            validate_coordinate(lat.0, lon.0)?;
+        }
+
+        if points.first() != points.last() {
+            points.push(points[0].clone());
+        }
+
+        Ok(GeoPolygon(points))
+    }
+}
+
+impl serde::Serialize for GeoPolygon {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let ring: Vec<[f32; 2]> = self.0.iter().map(|(lat, lon)| [lon.0, lat.0]).collect();
+        let mut state = serializer.serialize_struct("GeoPolygon", 2)?;
+        state.serialize_field("type", "Polygon")?;
+        state.serialize_field("coordinates", &[ring])?;
+        state.end()
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct GeoPolygonRaw {
+    r#type: String,
+    coordinates: Vec<Vec<[f32; 2]>>,
+}
+
+impl<'de> serde::Deserialize<'de> for GeoPolygon {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let raw = GeoPolygonRaw::deserialize(deserializer)?;
+        if raw.r#type != "Polygon" {
+            return Err(serde::de::Error::custom(format!(
+                "expected GeoJSON type 'Polygon', got '{}'",
+                raw.r#type
+            )));
+        }
+
+        let ring = raw
+            .coordinates
+            .into_iter()
+            .next()
+            .ok_or_else(|| serde::de::Error::custom("polygon is missing its outer ring"))?;
+
+        let points = ring
+            .into_iter()
+            .map(|[lon, lat]| (Float::from(lat), Float::from(lon)))
+            .collect();
+
+        GeoPolygon::new(points).map_err(serde::de::Error::custom)
     }
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
 enum Geolocation {
     Point(GeoPoint),
     Polygon(GeoPolygon),
@@ -344,23 +574,53 @@ impl<T: Parse> IntoIterator for Map<T> {
     }
 }
 
+/// Declares a "forgiving" enum: recognized variants round-trip through
+/// their bare identifier, and any other string is preserved verbatim in
+/// `Unknown(String)` instead of failing to parse. This lets a spec naming
+/// a newer provider, scope, etc. still load, and keeps the original text
+/// around so it survives a load/save cycle.
+macro_rules! forgiving_enum {
+    ($name:ident { $($variant:ident),+ $(,)? }) => {
+        impl ::std::str::FromStr for $name {
+            type Err = ::std::convert::Infallible;
+
+            fn from_str(value: &str) -> Result<Self, Self::Err> {
+                Ok(match value {
+                    $(stringify!($variant) => $name::$variant,)+
+                    other => $name::Unknown(other.to_string()),
+                })
+            }
+        }
+
+        impl ::std::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                match self {
+                    $($name::$variant => write!(f, stringify!($variant)),)+
+                    $name::Unknown(value) => write!(f, "{value}"),
+                }
+            }
+        }
+
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.to_string())
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let value = String::deserialize(deserializer)?;
+                Ok(value.parse().expect("FromStr is infallible for forgiving enums"))
+            }
+        }
+    };
+}
+
 /**
     Service
 **/
 
-#[derive(
-    Debug,
-    Clone,
-    PartialEq,
-    Eq,
-    PartialOrd,
-    Ord,
-    Hash,
-    strum::EnumString,
-    Default,
-    serde::Serialize,
-    serde::Deserialize,
-)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 enum Scope {
     #[default]
     Service,
@@ -378,8 +638,27 @@ enum Scope {
     PublicSafety,
     UrbanPlanning,
     Infrastructure,
+    Unknown(String),
 }
 
+forgiving_enum!(Scope {
+    Service,
+    Industry,
+    Manifacturing,
+    Education,
+    Healthcare,
+    SocialPrograms,
+    Government,
+    Energy,
+    Water,
+    Environment,
+    Transportation,
+    Communication,
+    PublicSafety,
+    UrbanPlanning,
+    Infrastructure,
+});
+
 #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 struct Version {
     major: Integer,
@@ -401,6 +680,32 @@ impl Version {
     }
 }
 
+impl PrimitiveParse for Version {
+    fn from_stream(stream: &str) -> Result<Self, anyhow::Error> {
+        let mut parts = stream.trim().split('.');
+
+        let major = parts
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("version '{stream}' is missing a major component"))?;
+        let minor = parts
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("version '{stream}' is missing a minor component"))?;
+        let patch = parts
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("version '{stream}' is missing a patch component"))?;
+
+        if parts.next().is_some() {
+            return Err(anyhow::anyhow!("version '{stream}' has too many components"));
+        }
+
+        Ok(Version {
+            major: Integer::from_stream(major)?,
+            minor: Integer::from_stream(minor)?,
+            patch: Integer::from_stream(patch)?,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 struct Service {
     version: Version,
@@ -409,8 +714,59 @@ struct Service {
 }
 
 impl Parse for Service {
-    fn parse(_stream: &str) -> Result<(&str, Self), anyhow::Error> {
-        todo!()
+    fn parse(stream: &str, start_line: u16) -> Result<(&str, u16, Self), anyhow::Error> {
+        let tokens = tokenize(stream, start_line)?;
+        let header = tokens
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("expected a 'service' block"))?;
+        let name = header
+            .content
+            .strip_suffix(':')
+            .ok_or_else(|| anyhow::anyhow!("line {}: expected a block header ending in ':'", header.number))?;
+
+        if name != "service" {
+            return Err(anyhow::anyhow!(
+                "line {}: expected 'service:', found '{name}:'",
+                header.number
+            ));
+        }
+
+        let end = block_end(&tokens, 1, header.indentation);
+        let body = &tokens[1..end];
+        let (tail, next_line) = tail_after(stream, &tokens, end, start_line);
+
+        let mut version = None;
+        let mut name_value = None;
+        let mut scope = None;
+
+        for entry in entries(body)? {
+            match entry {
+                Entry::Field { key: "name", value, .. } => name_value = Some(Text::from(value)),
+                Entry::Field { key: "version", value, line } => {
+                    version = Some(
+                        Version::from_stream(value)
+                            .map_err(|e| anyhow::anyhow!("line {line}: {e}"))?,
+                    )
+                }
+                Entry::Field { key: "scope", value, .. } => scope = Some(Scope::from_str(value)?),
+                Entry::Field { key, line, .. } => {
+                    return Err(anyhow::anyhow!("line {line}: unknown field '{key}'"))
+                }
+                Entry::Block { name, line, .. } => {
+                    return Err(anyhow::anyhow!("line {line}: unexpected block '{name}'"))
+                }
+            }
+        }
+
+        let service = Service {
+            version: version
+                .ok_or_else(|| anyhow::anyhow!("line {}: missing field 'version'", header.number))?,
+            name: name_value
+                .ok_or_else(|| anyhow::anyhow!("line {}: missing field 'name'", header.number))?,
+            scope: scope.unwrap_or_default(),
+        };
+
+        Ok((tail, next_line, service))
     }
 }
 
@@ -418,40 +774,27 @@ impl Parse for Service {
     Sensor Data
 **/
 
-#[derive(
-    Debug,
-    Clone,
-    PartialEq,
-    Eq,
-    PartialOrd,
-    Ord,
-    Hash,
-    strum::EnumString,
-    Default,
-    serde::Serialize,
-    serde::Deserialize,
-)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Provider {
     Fiware,
     Dataskop,
     Fotec,
-    #[default]
-    Unknown,
+    Unknown(String),
 }
 
-#[derive(
-    Debug,
-    Clone,
-    PartialEq,
-    Eq,
-    PartialOrd,
-    Ord,
-    Hash,
-    strum::EnumString,
-    Default,
-    serde::Serialize,
-    serde::Deserialize,
-)]
+impl Default for Provider {
+    fn default() -> Self {
+        Provider::Unknown(String::new())
+    }
+}
+
+forgiving_enum!(Provider {
+    Fiware,
+    Dataskop,
+    Fotec,
+});
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 enum SourceType {
     Sensor,
     Actuator,
@@ -463,8 +806,21 @@ enum SourceType {
     Robot,
     #[default]
     Other,
+    Unknown(String),
 }
 
+forgiving_enum!(SourceType {
+    Sensor,
+    Actuator,
+    Device,
+    Application,
+    Person,
+    Vehicle,
+    Animal,
+    Robot,
+    Other,
+});
+
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 struct Query {
     r#type: Text,
@@ -481,9 +837,93 @@ struct Measurement {
 }
 
 impl Parse for Measurement {
-    fn parse(_stream: &str) -> Result<(&str, Self), anyhow::Error> {
-        todo!()
+    fn parse(stream: &str, start_line: u16) -> Result<(&str, u16, Self), anyhow::Error> {
+        let tokens = tokenize(stream, start_line)?;
+        let header = tokens
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("expected a measurement block"))?;
+        let name = header
+            .content
+            .strip_suffix(':')
+            .ok_or_else(|| anyhow::anyhow!("line {}: expected a block header ending in ':'", header.number))?;
+
+        let end = block_end(&tokens, 1, header.indentation);
+        let body = &tokens[1..end];
+        let (tail, next_line) = tail_after(stream, &tokens, end, start_line);
+
+        let measurement = Measurement::from_entries(name, entries(body)?, header.number)?;
+
+        Ok((tail, next_line, measurement))
+    }
+}
+
+impl Measurement {
+    fn from_entries(
+        name: &str,
+        entries: Vec<Entry>,
+        header_line: u16,
+    ) -> Result<Self, anyhow::Error> {
+        let mut provider = None;
+        let mut source_type = None;
+        let mut uri = None;
+        let mut query = None;
+
+        for entry in entries {
+            match entry {
+                Entry::Field { key: "provider", value, .. } => {
+                    provider = Some(Provider::from_str(value)?)
+                }
+                Entry::Field { key: "type", value, .. } => {
+                    source_type = Some(SourceType::from_str(value)?)
+                }
+                Entry::Field { key: "uri", value, line } => {
+                    uri = Some(
+                        Uri::from_stream(value).map_err(|e| anyhow::anyhow!("line {line}: {e}"))?,
+                    )
+                }
+                Entry::Field { key, line, .. } => {
+                    return Err(anyhow::anyhow!("line {line}: unknown field '{key}'"))
+                }
+                Entry::Block { name: "query", body, .. } => query = Some(parse_query(body)?),
+                Entry::Block { name, line, .. } => {
+                    return Err(anyhow::anyhow!("line {line}: unexpected block '{name}'"))
+                }
+            }
+        }
+
+        Ok(Measurement {
+            name: Text::from(name),
+            provider: provider.unwrap_or_default(),
+            r#type: source_type.unwrap_or_default(),
+            uri: uri.ok_or_else(|| anyhow::anyhow!("line {header_line}: missing field 'uri'"))?,
+            query: query.ok_or_else(|| anyhow::anyhow!("line {header_line}: missing block 'query'"))?,
+        })
+    }
+}
+
+fn parse_query(body: &[LineInfo]) -> Result<Query, anyhow::Error> {
+    let mut r#type = None;
+    let mut select = None;
+
+    for entry in entries(body)? {
+        match entry {
+            Entry::Field { key: "type", value, .. } => r#type = Some(Text::from(value)),
+            Entry::Field { key, line, .. } => {
+                return Err(anyhow::anyhow!("line {line}: unknown field '{key}'"))
+            }
+            Entry::Block { name: "select", body, .. } => {
+                select = Some(Array(body.iter().map(|line| Text::from(line.content)).collect()))
+            }
+            Entry::Block { name, line, .. } => {
+                return Err(anyhow::anyhow!("line {line}: unexpected block '{name}'"))
+            }
+        }
     }
+
+    Ok(Query {
+        r#type: r#type.ok_or_else(|| anyhow::anyhow!("query is missing field 'type'"))?,
+        select: select.unwrap_or_default(),
+    })
 }
 
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
@@ -500,91 +940,400 @@ impl DataSources {
 }
 
 /*
-   Application Data
+   Data access
+
+   Executes a `Measurement`'s `Query` against its backing `Provider` and
+   projects the result down to the selected attributes. Dispatch is by
+   `Provider` so that `Dataskop`/`Fotec` backends can be added alongside
+   `Fiware` without touching call sites.
 */
 
-#[derive(
-    Debug,
-    Clone,
-    PartialEq,
-    Eq,
-    PartialOrd,
-    Ord,
-    Hash,
-    strum::EnumString,
-    Default,
-    serde::Serialize,
-    serde::Deserialize,
-)]
-enum RoleHierarchy {
-    #[default]
-    User,
-    Superuser,
-    Admin,
-}
+const NGSI_PAGE_SIZE: usize = 100;
 
-#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
-struct Role {
-    name: Text,
-    hierarchy: RoleHierarchy,
+#[async_trait::async_trait]
+trait DataSource {
+    async fn fetch(&self, measurement: &Measurement) -> Result<Vec<Map<Text>>, anyhow::Error>;
 }
 
-#[derive(Debug, Clone, Default, strum::EnumString, serde::Serialize, serde::Deserialize)]
-enum Roles {
-    #[default]
-    User,
-    Superuser,
-    Admin,
-    Custom(Role),
+struct FiwareDataSource {
+    client: reqwest::Client,
 }
 
-impl Parse for Roles {
-    fn parse(_stream: &str) -> Result<(&str, Self), anyhow::Error> {
-        todo!()
+impl FiwareDataSource {
+    fn new() -> Self {
+        FiwareDataSource {
+            client: reqwest::Client::new(),
+        }
     }
-}
 
-#[derive(Debug, Clone, Default, strum::EnumString, serde::Serialize, serde::Deserialize)]
-enum VisType {
-    Line,
-    Bar,
-    Pie,
-    Table,
-    Map,
-    #[default]
-    Other,
+    async fn fetch_page(
+        &self,
+        measurement: &Measurement,
+        offset: usize,
+    ) -> Result<Vec<serde_json::Value>, anyhow::Error> {
+        let attrs = measurement
+            .query
+            .select
+            .clone()
+            .into_iter()
+            .map(|attr| attr.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let response = self
+            .client
+            .get(measurement.uri.as_str())
+            .query(&[
+                ("type", measurement.query.r#type.to_string()),
+                ("attrs", attrs),
+                ("limit", NGSI_PAGE_SIZE.to_string()),
+                ("offset", offset.to_string()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(response.json::<Vec<serde_json::Value>>().await?)
+    }
 }
 
-#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
-struct Vis {
-    name: Text,
-    r#type: VisType,
-    source: Text,
-    data: Array<Text>,
-    extra: Option<Map<Text>>,
+fn ngsi_attribute_text(entity: &serde_json::Value, attr: &str) -> Option<Text> {
+    let value = entity.get(attr)?.get("value")?;
+    match value {
+        serde_json::Value::String(text) => Some(Text::from(text.as_str())),
+        other => Some(Text::from(other.to_string())),
+    }
 }
 
-impl Parse for Vis {
-    fn parse(_stream: &str) -> Result<(&str, Self), anyhow::Error> {
-        todo!()
+fn ngsi_location(entity: &serde_json::Value, attr: &str) -> Option<Geolocation> {
+    let value = entity.get(attr)?.get("value")?;
+    let coordinates = value.get("coordinates")?.as_array()?;
+
+    match value.get("type")?.as_str()? {
+        "Point" => {
+            let lon = coordinates.first()?.as_f64()? as f32;
+            let lat = coordinates.get(1)?.as_f64()? as f32;
+            GeoPoint::new(lat, lon).ok().map(Geolocation::Point)
+        }
+        "Polygon" => {
+            let ring = coordinates.first()?.as_array()?;
+            let points = ring
+                .iter()
+                .filter_map(|point| {
+                    let point = point.as_array()?;
+                    let lon = point.first()?.as_f64()? as f32;
+                    let lat = point.get(1)?.as_f64()? as f32;
+                    Some((Float::from(lat), Float::from(lon)))
+                })
+                .collect();
+            GeoPolygon::new(points).ok().map(Geolocation::Polygon)
+        }
+        _ => None,
     }
 }
 
+fn ngsi_entity_row(entity: &serde_json::Value, select: &Array<Text>) -> Map<Text> {
+    let mut row = Map::new();
+
+    for attr in select.clone().into_iter() {
+        let name = attr.to_string();
+
+        if name == "location" {
+            if let Some(location) = ngsi_location(entity, &name) {
+                let text = serde_json::to_string(&location).unwrap_or_default();
+                row.insert(attr, Text::from(text));
+            }
+            continue;
+        }
+
+        if let Some(text) = ngsi_attribute_text(entity, &name) {
+            row.insert(attr, text);
+        }
+    }
+
+    row
+}
+
+#[async_trait::async_trait]
+impl DataSource for FiwareDataSource {
+    async fn fetch(&self, measurement: &Measurement) -> Result<Vec<Map<Text>>, anyhow::Error> {
+        let mut rows = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            let page = self.fetch_page(measurement, offset).await?;
+            let page_len = page.len();
+
+            rows.extend(
+                page.iter()
+                    .map(|entity| ngsi_entity_row(entity, &measurement.query.select)),
+            );
+
+            if page_len < NGSI_PAGE_SIZE {
+                break;
+            }
+
+            offset += NGSI_PAGE_SIZE;
+        }
+
+        Ok(rows)
+    }
+}
+
+fn data_source_for(provider: &Provider) -> Result<Box<dyn DataSource + Send + Sync>, anyhow::Error> {
+    match provider {
+        Provider::Fiware => Ok(Box::new(FiwareDataSource::new())),
+        other => Err(anyhow::anyhow!("no data source implemented for {other}")),
+    }
+}
+
+impl Measurement {
+    async fn fetch(&self) -> Result<Vec<Map<Text>>, anyhow::Error> {
+        data_source_for(&self.provider)?.fetch(self).await
+    }
+}
+
+/*
+   Application Data
+*/
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+enum RoleHierarchy {
+    #[default]
+    User,
+    Superuser,
+    Admin,
+    Unknown(String),
+}
+
+forgiving_enum!(RoleHierarchy {
+    User,
+    Superuser,
+    Admin,
+});
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+struct Role {
+    name: Text,
+    hierarchy: RoleHierarchy,
+}
+
 #[derive(Debug, Clone, Default, strum::EnumString, serde::Serialize, serde::Deserialize)]
+enum Roles {
+    #[default]
+    User,
+    Superuser,
+    Admin,
+    Custom(Role),
+}
+
+impl Parse for Roles {
+    fn parse(stream: &str, start_line: u16) -> Result<(&str, u16, Self), anyhow::Error> {
+        let tokens = tokenize(stream, start_line)?;
+        let header = tokens
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("expected a role entry"))?;
+
+        let end = if header.content.ends_with(':') {
+            block_end(&tokens, 1, header.indentation)
+        } else {
+            1
+        };
+
+        let (tail, next_line) = tail_after(stream, &tokens, end, start_line);
+
+        let role = roles_from_body(&tokens[0..end])?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("line {}: empty role entry", header.number))?;
+
+        Ok((tail, next_line, role))
+    }
+}
+
+fn roles_from_body(body: &[LineInfo]) -> Result<Vec<Roles>, anyhow::Error> {
+    let mut roles = Vec::new();
+    let mut i = 0;
+
+    while i < body.len() {
+        let line = &body[i];
+
+        if let Some(name) = line.content.strip_suffix(':') {
+            let end = block_end(body, i + 1, line.indentation);
+
+            if name != "Custom" {
+                return Err(anyhow::anyhow!("line {}: unknown role block '{name}'", line.number));
+            }
+
+            let mut role_name = None;
+            let mut hierarchy = None;
+
+            for entry in entries(&body[i + 1..end])? {
+                match entry {
+                    Entry::Field { key: "name", value, .. } => role_name = Some(Text::from(value)),
+                    Entry::Field { key: "hierarchy", value, .. } => {
+                        hierarchy = Some(RoleHierarchy::from_str(value)?)
+                    }
+                    Entry::Field { key, line, .. } => {
+                        return Err(anyhow::anyhow!("line {line}: unknown field '{key}'"))
+                    }
+                    Entry::Block { name, line, .. } => {
+                        return Err(anyhow::anyhow!("line {line}: unexpected block '{name}'"))
+                    }
+                }
+            }
+
+            roles.push(Roles::Custom(Role {
+                name: role_name
+                    .ok_or_else(|| anyhow::anyhow!("line {}: missing field 'name'", line.number))?,
+                hierarchy: hierarchy.unwrap_or_default(),
+            }));
+            i = end;
+        } else {
+            let role = match line.content {
+                "User" => Roles::User,
+                "Superuser" => Roles::Superuser,
+                "Admin" => Roles::Admin,
+                other => return Err(anyhow::anyhow!("line {}: unknown role '{other}'", line.number)),
+            };
+            roles.push(role);
+            i += 1;
+        }
+    }
+
+    Ok(roles)
+}
+
+#[derive(Debug, Clone, Default)]
+enum VisType {
+    Line,
+    Bar,
+    Pie,
+    Table,
+    Map,
+    #[default]
+    Other,
+    Unknown(String),
+}
+
+forgiving_enum!(VisType {
+    Line,
+    Bar,
+    Pie,
+    Table,
+    Map,
+    Other,
+});
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+struct Vis {
+    name: Text,
+    r#type: VisType,
+    source: Text,
+    data: Array<Text>,
+    extra: Option<Map<Text>>,
+}
+
+impl Parse for Vis {
+    fn parse(stream: &str, start_line: u16) -> Result<(&str, u16, Self), anyhow::Error> {
+        let tokens = tokenize(stream, start_line)?;
+        let header = tokens
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("expected a visualization block"))?;
+        let name = header
+            .content
+            .strip_suffix(':')
+            .ok_or_else(|| anyhow::anyhow!("line {}: expected a block header ending in ':'", header.number))?;
+
+        let end = block_end(&tokens, 1, header.indentation);
+        let body = &tokens[1..end];
+        let (tail, next_line) = tail_after(stream, &tokens, end, start_line);
+
+        let vis = Vis::from_entries(name, entries(body)?, header.number)?;
+
+        Ok((tail, next_line, vis))
+    }
+}
+
+impl Vis {
+    fn from_entries(
+        name: &str,
+        entries: Vec<Entry>,
+        header_line: u16,
+    ) -> Result<Self, anyhow::Error> {
+        let mut vis_type = None;
+        let mut source = None;
+        let mut data = None;
+        let mut extra = None;
+
+        for entry in entries {
+            match entry {
+                Entry::Field { key: "type", value, .. } => {
+                    vis_type = Some(VisType::from_str(value)?)
+                }
+                Entry::Field { key: "source", value, .. } => source = Some(Text::from(value)),
+                Entry::Field { key, line, .. } => {
+                    return Err(anyhow::anyhow!("line {line}: unknown field '{key}'"))
+                }
+                Entry::Block { name: "data", body, .. } => {
+                    data = Some(Array(body.iter().map(|line| Text::from(line.content)).collect()))
+                }
+                Entry::Block { name: "extra", body, .. } => extra = Some(parse_text_map(body)?),
+                Entry::Block { name, line, .. } => {
+                    return Err(anyhow::anyhow!("line {line}: unexpected block '{name}'"))
+                }
+            }
+        }
+
+        Ok(Vis {
+            name: Text::from(name),
+            r#type: vis_type.unwrap_or_default(),
+            source: source
+                .ok_or_else(|| anyhow::anyhow!("line {header_line}: missing field 'source'"))?,
+            data: data.unwrap_or_default(),
+            extra,
+        })
+    }
+}
+
+fn parse_text_map(body: &[LineInfo]) -> Result<Map<Text>, anyhow::Error> {
+    let mut map = Map::new();
+
+    for entry in entries(body)? {
+        match entry {
+            Entry::Field { key, value, .. } => {
+                map.insert(key, Text::from(value));
+            }
+            Entry::Block { name, line, .. } => {
+                return Err(anyhow::anyhow!("line {line}: unexpected block '{name}'"))
+            }
+        }
+    }
+
+    Ok(map)
+}
+
+#[derive(Debug, Clone, Default)]
 enum AppType {
     #[default]
     Web,
     Mobile,
     Desktop,
+    Unknown(String),
 }
 
-#[derive(Debug, Clone, Default, strum::EnumString, serde::Serialize, serde::Deserialize)]
+forgiving_enum!(AppType { Web, Mobile, Desktop });
+
+#[derive(Debug, Clone, Default)]
 enum LayoutType {
     #[default]
     SinglePage,
     Pwa,
+    Unknown(String),
 }
 
+forgiving_enum!(LayoutType { SinglePage, Pwa });
+
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 struct Application {
     r#type: AppType,
@@ -604,7 +1353,7 @@ impl Application {
    Deployment Data
 */
 
-#[derive(Debug, Clone, Default, strum::EnumString, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Default)]
 enum DeploymentType {
     #[default]
     Docker,
@@ -613,19 +1362,103 @@ enum DeploymentType {
     Mesos,
     Nomad,
     Other,
+    Unknown(String),
 }
 
+forgiving_enum!(DeploymentType {
+    Docker,
+    Kubernetes,
+    Swarm,
+    Mesos,
+    Nomad,
+    Other,
+});
+
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 struct DeploymentEnv {
     name: Text,
     uri: Uri,
     port: Integer,
     r#type: DeploymentType,
+    replicas: Integer,
+    environment: Map<Text>,
 }
 
 impl Parse for DeploymentEnv {
-    fn parse(_stream: &str) -> Result<(&str, Self), anyhow::Error> {
-        todo!()
+    fn parse(stream: &str, start_line: u16) -> Result<(&str, u16, Self), anyhow::Error> {
+        let tokens = tokenize(stream, start_line)?;
+        let header = tokens
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("expected a deployment environment block"))?;
+        let name = header
+            .content
+            .strip_suffix(':')
+            .ok_or_else(|| anyhow::anyhow!("line {}: expected a block header ending in ':'", header.number))?;
+
+        let end = block_end(&tokens, 1, header.indentation);
+        let body = &tokens[1..end];
+        let (tail, next_line) = tail_after(stream, &tokens, end, start_line);
+
+        let env = DeploymentEnv::from_entries(name, entries(body)?, header.number)?;
+
+        Ok((tail, next_line, env))
+    }
+}
+
+impl DeploymentEnv {
+    fn from_entries(
+        name: &str,
+        entries: Vec<Entry>,
+        header_line: u16,
+    ) -> Result<Self, anyhow::Error> {
+        let mut uri = None;
+        let mut port = None;
+        let mut deployment_type = None;
+        let mut replicas = None;
+        let mut environment = None;
+
+        for entry in entries {
+            match entry {
+                Entry::Field { key: "uri", value, line } => {
+                    uri = Some(
+                        Uri::from_stream(value).map_err(|e| anyhow::anyhow!("line {line}: {e}"))?,
+                    )
+                }
+                Entry::Field { key: "port", value, line } => {
+                    port = Some(
+                        Integer::from_stream(value)
+                            .map_err(|e| anyhow::anyhow!("line {line}: {e}"))?,
+                    )
+                }
+                Entry::Field { key: "type", value, .. } => {
+                    deployment_type = Some(DeploymentType::from_str(value)?)
+                }
+                Entry::Field { key: "replicas", value, line } => {
+                    replicas = Some(
+                        Integer::from_stream(value)
+                            .map_err(|e| anyhow::anyhow!("line {line}: {e}"))?,
+                    )
+                }
+                Entry::Field { key, line, .. } => {
+                    return Err(anyhow::anyhow!("line {line}: unknown field '{key}'"))
+                }
+                Entry::Block { name: "environment", body, .. } => {
+                    environment = Some(parse_text_map(body)?)
+                }
+                Entry::Block { name, line, .. } => {
+                    return Err(anyhow::anyhow!("line {line}: unexpected block '{name}'"))
+                }
+            }
+        }
+
+        Ok(DeploymentEnv {
+            name: Text::from(name),
+            uri: uri.ok_or_else(|| anyhow::anyhow!("line {header_line}: missing field 'uri'"))?,
+            port: port.ok_or_else(|| anyhow::anyhow!("line {header_line}: missing field 'port'"))?,
+            r#type: deployment_type.unwrap_or_default(),
+            replicas: replicas.unwrap_or(Integer::from(1)),
+            environment: environment.unwrap_or_default(),
+        })
     }
 }
 
@@ -642,27 +1475,769 @@ struct SmartService {
     deployment: Deployment,
 }
 
+/*
+   Deployment emitters
+
+   Turn a `Deployment` (plus the owning `Service`) into concrete deployment
+   artifacts: a docker-compose-style spec for the Docker/Swarm variants, and
+   Kubernetes `Deployment`/`Service` manifests for the Kubernetes variant.
+   The builder shape mirrors shiplift's `ServiceOptions`/`ServiceSpec`.
+*/
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+struct ComposePort {
+    published: u32,
+    target: u32,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct ComposeDeploy {
+    replicas: u32,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct ComposeService {
+    image: String,
+    ports: Vec<ComposePort>,
+    environment: HashMap<String, String>,
+    labels: HashMap<String, String>,
+    deploy: ComposeDeploy,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct ComposeSpec {
+    version: String,
+    services: HashMap<String, ComposeService>,
+}
+
+struct ComposeServiceBuilder {
+    image: String,
+    ports: Vec<ComposePort>,
+    environment: HashMap<String, String>,
+    labels: HashMap<String, String>,
+    replicas: u32,
+}
+
+impl ComposeServiceBuilder {
+    fn new(image: impl Into<String>) -> Self {
+        ComposeServiceBuilder {
+            image: image.into(),
+            ports: Vec::new(),
+            environment: HashMap::new(),
+            labels: HashMap::new(),
+            replicas: 1,
+        }
+    }
+
+    fn expose(mut self, port: u32) -> Self {
+        self.ports.push(ComposePort {
+            published: port,
+            target: port,
+        });
+        self
+    }
+
+    fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    fn environment(mut self, environment: HashMap<String, String>) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    fn replicas(mut self, replicas: u32) -> Self {
+        self.replicas = replicas;
+        self
+    }
+
+    fn build(self) -> ComposeService {
+        ComposeService {
+            image: self.image,
+            ports: self.ports,
+            environment: self.environment,
+            labels: self.labels,
+            deploy: ComposeDeploy {
+                replicas: self.replicas,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct K8sMetadata {
+    name: String,
+    labels: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct K8sContainerPort {
+    container_port: u32,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct K8sEnvVar {
+    name: String,
+    value: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct K8sContainer {
+    name: String,
+    image: String,
+    ports: Vec<K8sContainerPort>,
+    env: Vec<K8sEnvVar>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct K8sPodSpec {
+    containers: Vec<K8sContainer>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct K8sPodTemplate {
+    metadata: K8sMetadata,
+    spec: K8sPodSpec,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct K8sLabelSelector {
+    match_labels: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct K8sDeploymentSpec {
+    replicas: u32,
+    selector: K8sLabelSelector,
+    template: K8sPodTemplate,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct K8sDeployment {
+    #[serde(rename = "apiVersion")]
+    api_version: String,
+    kind: String,
+    metadata: K8sMetadata,
+    spec: K8sDeploymentSpec,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct K8sServicePort {
+    port: u32,
+    target_port: u32,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct K8sServiceSpec {
+    selector: HashMap<String, String>,
+    ports: Vec<K8sServicePort>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct K8sService {
+    #[serde(rename = "apiVersion")]
+    api_version: String,
+    kind: String,
+    metadata: K8sMetadata,
+    spec: K8sServiceSpec,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum K8sManifest {
+    Deployment(K8sDeployment),
+    Service(K8sService),
+}
+
+fn slugify(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect()
+}
+
+fn image_tag(service: &Service) -> String {
+    format!(
+        "{}:{}.{}.{}",
+        slugify(&service.name.to_string()),
+        service.version.major,
+        service.version.minor,
+        service.version.patch
+    )
+}
+
+impl Deployment {
+    fn to_compose(&self, service: &Service) -> ComposeSpec {
+        let image = image_tag(service);
+        let mut services = HashMap::new();
+
+        for env in self.env.0.values() {
+            if !matches!(env.r#type, DeploymentType::Docker | DeploymentType::Swarm) {
+                continue;
+            }
+
+            let environment = env
+                .environment
+                .clone()
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect();
+
+            let built = ComposeServiceBuilder::new(image.clone())
+                .expose(*env.port)
+                .label("service.name", service.name.to_string())
+                .label("service.version", image_tag(service))
+                .environment(environment)
+                .replicas(*env.replicas)
+                .build();
+
+            services.insert(slugify(&env.name.to_string()), built);
+        }
+
+        ComposeSpec {
+            version: "3.8".to_string(),
+            services,
+        }
+    }
+
+    fn to_k8s_manifests(&self, service: &Service) -> Vec<K8sManifest> {
+        let image = image_tag(service);
+        let mut manifests = Vec::new();
+
+        for env in self.env.0.values() {
+            if !matches!(env.r#type, DeploymentType::Kubernetes) {
+                continue;
+            }
+
+            let name = slugify(&env.name.to_string());
+            let mut labels = HashMap::new();
+            labels.insert("app".to_string(), name.clone());
+
+            let metadata = K8sMetadata {
+                name: name.clone(),
+                labels: labels.clone(),
+            };
+
+            let env_vars = env
+                .environment
+                .clone()
+                .into_iter()
+                .map(|(key, value)| K8sEnvVar {
+                    name: key.to_string(),
+                    value: value.to_string(),
+                })
+                .collect();
+
+            manifests.push(K8sManifest::Deployment(K8sDeployment {
+                api_version: "apps/v1".to_string(),
+                kind: "Deployment".to_string(),
+                metadata: metadata.clone(),
+                spec: K8sDeploymentSpec {
+                    replicas: *env.replicas,
+                    selector: K8sLabelSelector {
+                        match_labels: labels.clone(),
+                    },
+                    template: K8sPodTemplate {
+                        metadata: metadata.clone(),
+                        spec: K8sPodSpec {
+                            containers: vec![K8sContainer {
+                                name: name.clone(),
+                                image: image.clone(),
+                                ports: vec![K8sContainerPort {
+                                    container_port: *env.port,
+                                }],
+                                env: env_vars,
+                            }],
+                        },
+                    },
+                },
+            }));
+
+            manifests.push(K8sManifest::Service(K8sService {
+                api_version: "v1".to_string(),
+                kind: "Service".to_string(),
+                metadata,
+                spec: K8sServiceSpec {
+                    selector: labels,
+                    ports: vec![K8sServicePort {
+                        port: *env.port,
+                        target_port: *env.port,
+                    }],
+                },
+            }));
+        }
+
+        manifests
+    }
+}
+
+/*
+   Web server
+
+   Serves a parsed `Application` as a running web app, mirroring flabk's
+   stack: warp for routing, handlebars for rendering, and rust-embed so a
+   parsed spec ships as a single deployable binary. One route per `Vis`
+   renders the chosen `VisType` through the shared template, fed by the
+   FIWARE connector; routes are gated by the `Application`'s `Roles`.
+*/
+
+#[derive(rust_embed::RustEmbed)]
+#[folder = "templates/"]
+struct Templates;
+
+#[derive(rust_embed::RustEmbed)]
+#[folder = "static/"]
+struct Assets;
+
+#[derive(rust_embed::RustEmbed)]
+#[folder = "pwa/"]
+struct PwaAssets;
+
+#[derive(Debug)]
+struct Forbidden;
+
+impl warp::reject::Reject for Forbidden {}
+
+#[derive(Debug)]
+struct RenderFailed;
+
+impl warp::reject::Reject for RenderFailed {}
+
+fn template_registry() -> Result<handlebars::Handlebars<'static>, anyhow::Error> {
+    let mut registry = handlebars::Handlebars::new();
+
+    for file in Templates::iter() {
+        let asset = Templates::get(&file)
+            .ok_or_else(|| anyhow::anyhow!("missing embedded template '{file}'"))?;
+        let source = std::str::from_utf8(asset.data.as_ref())?;
+        let name = file.trim_end_matches(".hbs").to_string();
+        registry.register_template_string(&name, source)?;
+    }
+
+    Ok(registry)
+}
+
+fn role_allowed(roles: &Array<Roles>, requested: &str) -> bool {
+    if roles.get(0).is_none() {
+        return true;
+    }
+
+    roles.clone().into_iter().any(|role| match role {
+        Roles::User => requested.eq_ignore_ascii_case("user"),
+        Roles::Superuser => requested.eq_ignore_ascii_case("superuser"),
+        Roles::Admin => requested.eq_ignore_ascii_case("admin"),
+        Roles::Custom(custom) => custom.name.to_string().eq_ignore_ascii_case(requested),
+    })
+}
+
+#[derive(Debug, serde::Serialize)]
+struct VisContext {
+    name: String,
+    r#type: String,
+    columns: Vec<String>,
+    rows: Vec<HashMap<String, String>>,
+    columns_json: String,
+    rows_json: String,
+    extra: HashMap<String, String>,
+    chart_kind: Option<&'static str>,
+    is_map: bool,
+}
+
+fn chart_kind_for(kind: &VisType) -> Option<&'static str> {
+    match kind {
+        VisType::Line => Some("line"),
+        VisType::Bar => Some("bar"),
+        VisType::Pie => Some("pie"),
+        _ => None,
+    }
+}
+
+/// Escapes `<` in a JSON string so it can be embedded inside a `<script>`
+/// block without a `</script>` in the data ending the tag early.
+///
+/// `rows`/`columns` come from `Measurement::fetch()`, i.e. from whatever the
+/// external entity store returns, so they must be treated as untrusted when
+/// interpolated into the vis template.
+fn escape_for_script(json: String) -> String {
+    json.replace('<', "\\u003c")
+}
+
+async fn render_vis(
+    vis: Vis,
+    measurement: Option<Measurement>,
+    registry: Arc<handlebars::Handlebars<'static>>,
+) -> Result<String, anyhow::Error> {
+    let columns = vis
+        .data
+        .clone()
+        .into_iter()
+        .map(|column| column.to_string())
+        .collect::<Vec<_>>();
+
+    let source_rows = match measurement {
+        Some(measurement) => measurement.fetch().await?,
+        None => Vec::new(),
+    };
+
+    let rows = source_rows
+        .into_iter()
+        .map(|row| {
+            columns
+                .iter()
+                .filter_map(|column| {
+                    row.get(column.as_str())
+                        .map(|value| (column.clone(), value.to_string()))
+                })
+                .collect::<HashMap<_, _>>()
+        })
+        .collect();
+
+    let extra = vis
+        .extra
+        .clone()
+        .map(|extra| {
+            extra
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let columns_json = escape_for_script(serde_json::to_string(&columns)?);
+    let rows_json = escape_for_script(serde_json::to_string(&rows)?);
+    let chart_kind = chart_kind_for(&vis.r#type);
+    let is_map = matches!(vis.r#type, VisType::Map);
+
+    let context = VisContext {
+        name: vis.name.to_string(),
+        r#type: vis.r#type.to_string(),
+        columns,
+        rows,
+        columns_json,
+        rows_json,
+        extra,
+        chart_kind,
+        is_map,
+    };
+
+    Ok(registry.render("vis", &context)?)
+}
+
+fn with_state<T: Clone + Send>(
+    value: T,
+) -> impl warp::Filter<Extract = (T,), Error = std::convert::Infallible> + Clone {
+    warp::any().map(move || value.clone())
+}
+
+/// Resolves the effective role for a request.
+///
+/// A client-supplied `x-role` header is only honored when it's accompanied by
+/// an `x-service-token` that matches the server's `SMART_SERVICE_AUTH_TOKEN`
+/// environment variable. Without a configured token, or with a missing or
+/// mismatched one, the requester is always treated as the unprivileged
+/// default role, regardless of what `x-role` claims.
+fn resolve_role(token: Option<String>, claimed_role: Option<String>) -> String {
+    let configured = std::env::var("SMART_SERVICE_AUTH_TOKEN").ok().filter(|value| !value.is_empty());
+
+    match (configured, token) {
+        (Some(expected), Some(supplied)) if supplied == expected => {
+            claimed_role.unwrap_or_else(|| "user".to_string())
+        }
+        _ => "user".to_string(),
+    }
+}
+
+async fn vis_handler(
+    name: String,
+    token: Option<String>,
+    role: Option<String>,
+    app: Application,
+    data_sources: DataSources,
+    registry: Arc<handlebars::Handlebars<'static>>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let effective_role = resolve_role(token, role);
+
+    if !role_allowed(&app.roles, &effective_role) {
+        return Err(warp::reject::custom(Forbidden));
+    }
+
+    let vis = app
+        .visualizations
+        .get(name.as_str())
+        .cloned()
+        .ok_or_else(warp::reject::not_found)?;
+
+    let measurement = data_sources.measurements.get(vis.source.clone()).cloned();
+
+    let html = render_vis(vis, measurement, registry)
+        .await
+        .map_err(|_| warp::reject::custom(RenderFailed))?;
+
+    Ok(warp::reply::html(html))
+}
+
+fn vis_routes(
+    app: Application,
+    data_sources: DataSources,
+    registry: Arc<handlebars::Handlebars<'static>>,
+) -> impl warp::Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    warp::path!("vis" / String)
+        .and(warp::header::optional::<String>("x-service-token"))
+        .and(warp::header::optional::<String>("x-role"))
+        .and(with_state(app))
+        .and(with_state(data_sources))
+        .and(with_state(registry))
+        .and_then(vis_handler)
+}
+
+fn pwa_routes(
+    layout: LayoutType,
+) -> impl warp::Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+    let enabled = matches!(layout, LayoutType::Pwa);
+
+    let manifest = warp::path("manifest.json")
+        .and(with_state(enabled))
+        .and_then(|enabled: bool| async move {
+            if !enabled {
+                return Err(warp::reject::not_found());
+            }
+
+            let asset = PwaAssets::get("manifest.json").ok_or_else(warp::reject::not_found)?;
+            Ok(warp::reply::with_header(
+                asset.data.into_owned(),
+                "content-type",
+                "application/manifest+json",
+            ))
+        });
+
+    let service_worker = warp::path("sw.js")
+        .and(with_state(enabled))
+        .and_then(|enabled: bool| async move {
+            if !enabled {
+                return Err(warp::reject::not_found());
+            }
+
+            let asset = PwaAssets::get("sw.js").ok_or_else(warp::reject::not_found)?;
+            Ok(warp::reply::with_header(
+                asset.data.into_owned(),
+                "content-type",
+                "application/javascript",
+            ))
+        });
+
+    manifest.or(service_worker)
+}
+
+async fn handle_rejection(
+    rejection: warp::Rejection,
+) -> Result<impl warp::Reply, std::convert::Infallible> {
+    let status = if rejection.is_not_found() {
+        warp::http::StatusCode::NOT_FOUND
+    } else if rejection.find::<Forbidden>().is_some() {
+        warp::http::StatusCode::FORBIDDEN
+    } else {
+        warp::http::StatusCode::INTERNAL_SERVER_ERROR
+    };
+
+    Ok(warp::reply::with_status(status.canonical_reason().unwrap_or(""), status))
+}
+
+async fn serve(service: SmartService, addr: std::net::SocketAddr) -> Result<(), anyhow::Error> {
+    let registry = Arc::new(template_registry()?);
+
+    let routes = vis_routes(
+        service.application.clone(),
+        service.data_sources.clone(),
+        registry,
+    )
+    .or(pwa_routes(service.application.layout.clone()))
+    .or(warp_embed::embed(&Assets))
+    .recover(handle_rejection);
+
+    warp::serve(routes).run(addr).await;
+
+    Ok(())
+}
+
 /*
    Parser
 */
 
-struct LineInfo {
-    content: &'static str,
+struct LineInfo<'a> {
+    content: &'a str,
     number: u16,
     indentation: u8,
+    offset: usize,
+}
+
+/// Splits `source` into non-blank, indentation-annotated lines.
+///
+/// `offset` points at the start of the *raw* line (before any leading
+/// whitespace is stripped) so that a sub-slice starting at a given token
+/// can be re-tokenized on its own and still see the original, absolute
+/// indentation of every line it contains.
+///
+/// `start_line` is the line number `source`'s first line should be counted
+/// as. Callers that re-tokenize a tail slice of a larger document pass the
+/// line that tail actually starts at, so that `number` stays an absolute
+/// line number into the original document instead of resetting to 1.
+fn tokenize(source: &str, start_line: u16) -> Result<Vec<LineInfo<'_>>, anyhow::Error> {
+    let mut tokens = Vec::new();
+    let mut offset = 0;
+
+    for (index, raw_line) in source.split('\n').enumerate() {
+        let number = start_line + index as u16;
+        let line_offset = offset;
+        offset += raw_line.len() + 1;
+
+        let line = raw_line.strip_suffix('\r').unwrap_or(raw_line);
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let leading = &line[..line.len() - line.trim_start().len()];
+
+        if leading.contains(' ') && leading.contains('\t') {
+            return Err(anyhow::anyhow!(
+                "line {number}: indentation mixes tabs and spaces"
+            ));
+        }
+
+        tokens.push(LineInfo {
+            content: trimmed,
+            number,
+            indentation: leading.chars().count() as u8,
+            offset: line_offset,
+        });
+    }
+
+    Ok(tokens)
+}
+
+/// Returns the index, relative to `tokens`, of the first token at or past
+/// `start` whose indentation is not greater than `indentation` - i.e. the
+/// end of the body belonging to a header at that indentation.
+fn block_end(tokens: &[LineInfo<'_>], start: usize, indentation: u8) -> usize {
+    let mut end = start;
+
+    while end < tokens.len() && tokens[end].indentation > indentation {
+        end += 1;
+    }
+
+    end
+}
+
+/// Returns the unparsed remainder of `stream` starting at the token at
+/// index `end` (the first token not consumed), or `""` when there is none,
+/// together with the line number it starts at - the `start_line` a caller
+/// should re-tokenize that remainder with so line numbers stay absolute.
+fn tail_after<'a>(
+    stream: &'a str,
+    tokens: &[LineInfo<'_>],
+    end: usize,
+    start_line: u16,
+) -> (&'a str, u16) {
+    match tokens.get(end) {
+        Some(token) => (&stream[token.offset..], token.number),
+        None => ("", start_line + stream.split('\n').count() as u16),
+    }
 }
 
-struct Parser {
+/// One line of a block's body: either a `key is value` field or a nested
+/// `name:` block together with the token slice making up its own body.
+enum Entry<'a, 'b> {
+    Field {
+        key: &'a str,
+        value: &'a str,
+        line: u16,
+    },
+    Block {
+        name: &'a str,
+        body: &'b [LineInfo<'a>],
+        line: u16,
+    },
+}
+
+/// Walks the immediate children of a block body, yielding one `Entry` per
+/// field or nested block. Grandchildren of a nested block are consumed as
+/// part of that block's `body` slice rather than yielded directly.
+fn entries<'a, 'b>(body: &'b [LineInfo<'a>]) -> Result<Vec<Entry<'a, 'b>>, anyhow::Error> {
+    let mut out = Vec::new();
+    let mut i = 0;
+
+    while i < body.len() {
+        let line = &body[i];
+
+        if let Some(name) = line.content.strip_suffix(':') {
+            let end = block_end(body, i + 1, line.indentation);
+            out.push(Entry::Block {
+                name,
+                body: &body[i + 1..end],
+                line: line.number,
+            });
+            i = end;
+        } else if let Some((key, value)) = line.content.split_once(" is ") {
+            out.push(Entry::Field {
+                key,
+                value,
+                line: line.number,
+            });
+            i += 1;
+        } else {
+            return Err(anyhow::anyhow!(
+                "line {}: expected 'key is value' or a nested block",
+                line.number
+            ));
+        }
+    }
+
+    Ok(out)
+}
+
+/// Walks the immediate children of a body that holds a collection of named
+/// blocks (e.g. `measurements:`, `visualizations:`, `env:`), requiring each
+/// child to itself be a block, and collects the results of `from_entries`
+/// into a `Map` keyed by each child's name.
+fn parse_named_blocks<T: Parse>(
+    body: &[LineInfo<'_>],
+    expected: &str,
+    from_entries: impl Fn(&str, Vec<Entry>, u16) -> Result<T, anyhow::Error>,
+) -> Result<Map<T>, anyhow::Error> {
+    let mut out = Map::new();
+
+    for item in entries(body)? {
+        let Entry::Block { name, body, line } = item else {
+            return Err(anyhow::anyhow!("expected {expected}"));
+        };
+        let value = from_entries(name, entries(body)?, line)?;
+        out.insert(name, value);
+    }
+
+    Ok(out)
+}
+
+struct Parser<'a> {
     indentation: u8,
-    source: &'static str,
+    source: &'a str,
     service: Option<Service>,
     data_sources: Option<DataSources>,
     application: Option<Application>,
     deployment: Option<Deployment>,
 }
 
-impl Parser {
-    fn new(source: &'static str) -> Self {
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
         Self {
             indentation: 0,
             source,
@@ -673,6 +2248,147 @@ impl Parser {
         }
     }
 
+    fn parse(mut self) -> Result<SmartService, anyhow::Error> {
+        let (stream, line, service) = Service::parse(self.source, 1)?;
+        self.service = Some(service);
+
+        let (stream, line, data_sources) = Self::parse_data_sources(stream, line)?;
+        self.data_sources = Some(data_sources);
+
+        let (stream, line, application) = Self::parse_application(stream, line)?;
+        self.application = Some(application);
+
+        let (_, _, deployment) = Self::parse_deployment(stream, line)?;
+        self.deployment = Some(deployment);
+
+        self.smart_service()
+    }
+
+    fn parse_data_sources(stream: &str, start_line: u16) -> Result<(&str, u16, DataSources), anyhow::Error> {
+        let tokens = tokenize(stream, start_line)?;
+        let header = tokens
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("expected a 'data_sources' block"))?;
+
+        header
+            .content
+            .strip_suffix(':')
+            .filter(|name| *name == "data_sources")
+            .ok_or_else(|| anyhow::anyhow!("line {}: expected 'data_sources:'", header.number))?;
+
+        let end = block_end(&tokens, 1, header.indentation);
+        let body = &tokens[1..end];
+        let (tail, next_line) = tail_after(stream, &tokens, end, start_line);
+
+        let mut data_sources = DataSources::default();
+
+        for entry in entries(body)? {
+            match entry {
+                Entry::Block { name: "measurements", body, .. } => {
+                    data_sources.measurements =
+                        parse_named_blocks(body, "a measurement block", Measurement::from_entries)?;
+                }
+                Entry::Block { name, line, .. } => {
+                    return Err(anyhow::anyhow!("line {line}: unexpected block '{name}'"))
+                }
+                Entry::Field { key, line, .. } => {
+                    return Err(anyhow::anyhow!("line {line}: unexpected field '{key}'"))
+                }
+            }
+        }
+
+        Ok((tail, next_line, data_sources))
+    }
+
+    fn parse_application(stream: &str, start_line: u16) -> Result<(&str, u16, Application), anyhow::Error> {
+        let tokens = tokenize(stream, start_line)?;
+        let header = tokens
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("expected an 'application' block"))?;
+
+        header
+            .content
+            .strip_suffix(':')
+            .filter(|name| *name == "application")
+            .ok_or_else(|| anyhow::anyhow!("line {}: expected 'application:'", header.number))?;
+
+        let end = block_end(&tokens, 1, header.indentation);
+        let body = &tokens[1..end];
+        let (tail, next_line) = tail_after(stream, &tokens, end, start_line);
+
+        let mut app_type = None;
+        let mut layout = None;
+        let mut roles = Vec::new();
+        let mut visualizations = Map::new();
+
+        for entry in entries(body)? {
+            match entry {
+                Entry::Field { key: "type", value, .. } => {
+                    app_type = Some(AppType::from_str(value)?)
+                }
+                Entry::Field { key: "layout", value, .. } => {
+                    layout = Some(LayoutType::from_str(value)?)
+                }
+                Entry::Field { key, line, .. } => {
+                    return Err(anyhow::anyhow!("line {line}: unknown field '{key}'"))
+                }
+                Entry::Block { name: "roles", body, .. } => {
+                    roles.extend(roles_from_body(body)?);
+                }
+                Entry::Block { name: "visualizations", body, .. } => {
+                    visualizations = parse_named_blocks(body, "a visualization block", Vis::from_entries)?;
+                }
+                Entry::Block { name, line, .. } => {
+                    return Err(anyhow::anyhow!("line {line}: unexpected block '{name}'"))
+                }
+            }
+        }
+
+        let application = Application {
+            r#type: app_type.unwrap_or_default(),
+            layout: layout.unwrap_or_default(),
+            roles: Array::new(roles),
+            visualizations,
+        };
+
+        Ok((tail, next_line, application))
+    }
+
+    fn parse_deployment(stream: &str, start_line: u16) -> Result<(&str, u16, Deployment), anyhow::Error> {
+        let tokens = tokenize(stream, start_line)?;
+        let header = tokens
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("expected a 'deployment' block"))?;
+
+        header
+            .content
+            .strip_suffix(':')
+            .filter(|name| *name == "deployment")
+            .ok_or_else(|| anyhow::anyhow!("line {}: expected 'deployment:'", header.number))?;
+
+        let end = block_end(&tokens, 1, header.indentation);
+        let body = &tokens[1..end];
+        let (tail, next_line) = tail_after(stream, &tokens, end, start_line);
+
+        let mut env = Map::new();
+
+        for entry in entries(body)? {
+            match entry {
+                Entry::Block { name: "env", body, .. } => {
+                    env = parse_named_blocks(body, "a deployment environment block", DeploymentEnv::from_entries)?;
+                }
+                Entry::Block { name, line, .. } => {
+                    return Err(anyhow::anyhow!("line {line}: unexpected block '{name}'"))
+                }
+                Entry::Field { key, line, .. } => {
+                    return Err(anyhow::anyhow!("line {line}: unexpected field '{key}'"))
+                }
+            }
+        }
+
+        Ok((tail, next_line, Deployment { env }))
+    }
+
     fn smart_service(self) -> Result<SmartService, anyhow::Error> {
         Ok(SmartService {
             service: self
@@ -691,7 +2407,8 @@ impl Parser {
     }
 }
 
-fn main() {
+#[tokio::main]
+async fn main() -> Result<(), anyhow::Error> {
     let _service = Service {
         name: "Air Quality Madrid".into(),
         version: Version {
@@ -749,6 +2466,8 @@ fn main() {
                 uri: Uri::from_stream("http://localhost/test").unwrap_or_default(),
                 port: 50055.into(),
                 r#type: DeploymentType::Docker,
+                replicas: 1.into(),
+                environment: Map::default(),
             }
         }),
     };
@@ -760,9 +2479,8 @@ fn main() {
         deployment: _deployment,
     };
 
-    let _sr = serde_json::to_string(&_ss).unwrap_or_default();
-
-    println!("{_sr}");
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], 8080));
+    serve(_ss, addr).await
 }
 
 #[cfg(test)]
@@ -779,7 +2497,7 @@ mod tests {
             scope is Environment
         "#;
 
-        let Ok((_rest, service)) = Service::parse(raw) else { panic!("Could not parse") };
+        let Ok((_rest, _line, service)) = Service::parse(raw, 1) else { panic!("Could not parse") };
 
         let expected = Service {
             name: Text("Air Quality Madrid".into()),
@@ -790,18 +2508,307 @@ mod tests {
         assert_eq!(expected, service);
     }
 
+    #[test]
+    fn validate_coordinate_rejects_out_of_range_lat_lon() {
+        assert!(validate_coordinate(0.0, 0.0).is_ok());
+        assert!(validate_coordinate(90.0, 180.0).is_ok());
+        assert!(validate_coordinate(-90.0, -180.0).is_ok());
+        assert!(validate_coordinate(90.1, 0.0).is_err());
+        assert!(validate_coordinate(-90.1, 0.0).is_err());
+        assert!(validate_coordinate(0.0, 180.1).is_err());
+        assert!(validate_coordinate(0.0, -180.1).is_err());
+    }
+
+    #[test]
+    fn geo_polygon_closes_an_open_ring() {
+        let polygon = GeoPolygon::new(vec![
+            (Float(0.0), Float(0.0)),
+            (Float(0.0), Float(1.0)),
+            (Float(1.0), Float(1.0)),
+        ])
+        .expect("valid polygon");
+
+        assert_eq!(polygon.0.first(), polygon.0.last());
+        assert_eq!(polygon.0.len(), 4);
+    }
+
+    #[test]
+    fn geo_polygon_leaves_an_already_closed_ring_untouched() {
+        let polygon = GeoPolygon::new(vec![
+            (Float(0.0), Float(0.0)),
+            (Float(0.0), Float(1.0)),
+            (Float(1.0), Float(1.0)),
+            (Float(0.0), Float(0.0)),
+        ])
+        .expect("valid polygon");
+
+        assert_eq!(polygon.0.len(), 4);
+    }
+
+    #[test]
+    fn geo_polygon_rejects_out_of_range_points() {
+        let err = GeoPolygon::new(vec![(Float(0.0), Float(0.0)), (Float(200.0), Float(0.0))]);
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn deployment_emits_compose_and_k8s_manifests() {
+        let service = Service {
+            name: Text::from("Air Quality Madrid"),
+            version: Version::new(1, 2, 3),
+            scope: Scope::Environment,
+        };
+
+        let mut env = Map::new();
+        env.insert(
+            "local",
+            DeploymentEnv {
+                name: Text::from("local"),
+                uri: Uri::from_stream("http://localhost/test").unwrap(),
+                port: Integer::from(8080),
+                r#type: DeploymentType::Docker,
+                replicas: Integer::from(3),
+                environment: {
+                    let mut environment = Map::new();
+                    environment.insert("LOG_LEVEL", Text::from("debug"));
+                    environment
+                },
+            },
+        );
+        env.insert(
+            "cluster",
+            DeploymentEnv {
+                name: Text::from("cluster"),
+                uri: Uri::from_stream("http://cluster.local/test").unwrap(),
+                port: Integer::from(9090),
+                r#type: DeploymentType::Kubernetes,
+                replicas: Integer::from(2),
+                environment: Map::new(),
+            },
+        );
+
+        let deployment = Deployment { env };
+
+        let compose = deployment.to_compose(&service);
+        assert_eq!(compose.version, "3.8");
+        let local = compose
+            .services
+            .get("local")
+            .expect("compose service for 'local'");
+        assert_eq!(local.image, "air-quality-madrid:1.2.3");
+        assert_eq!(local.ports, vec![ComposePort { published: 8080, target: 8080 }]);
+        assert_eq!(local.deploy.replicas, 3);
+        assert_eq!(local.environment.get("LOG_LEVEL"), Some(&"debug".to_string()));
+        assert!(!compose.services.contains_key("cluster"));
+
+        let manifests = deployment.to_k8s_manifests(&service);
+        let deployment_manifest = manifests
+            .iter()
+            .find_map(|manifest| match manifest {
+                K8sManifest::Deployment(deployment) => Some(deployment),
+                K8sManifest::Service(_) => None,
+            })
+            .expect("a Deployment manifest");
+
+        assert_eq!(deployment_manifest.api_version, "apps/v1");
+        assert_eq!(deployment_manifest.kind, "Deployment");
+        assert_eq!(deployment_manifest.spec.replicas, 2);
+        assert_eq!(
+            deployment_manifest.spec.template.spec.containers[0].ports[0].container_port,
+            9090
+        );
+    }
+
+    #[test]
+    fn unknown_provider_round_trips() {
+        let provider = Provider::from_str("CustomCloud").unwrap();
+        assert_eq!(provider, Provider::Unknown("CustomCloud".to_string()));
+        assert_eq!(provider.to_string(), "CustomCloud");
+
+        let json = serde_json::to_string(&provider).unwrap();
+        assert_eq!(json, "\"CustomCloud\"");
+
+        let round_tripped: Provider = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_tripped, provider);
+    }
+
     #[test]
     fn parse_data_sources() {
-        println!("Data Sources");
+        let raw = r#"
+        data_sources:
+            measurements:
+                AirQuality:
+                    provider is Fiware
+                    type is Sensor
+                    uri is http://fiware.local/v2
+                    query:
+                        type is AirQualityObserved
+                        select:
+                            NOx
+                            O3
+        "#;
+
+        let Ok((_rest, _line, data_sources)) = Parser::parse_data_sources(raw, 1) else {
+            panic!("Could not parse")
+        };
+
+        let measurement = data_sources
+            .measurements
+            .get("AirQuality")
+            .expect("AirQuality measurement");
+
+        assert_eq!(measurement.provider, Provider::Fiware);
+        assert_eq!(measurement.r#type, SourceType::Sensor);
+        assert_eq!(measurement.query.r#type.to_string(), "AirQualityObserved");
+        assert_eq!(
+            measurement.query.select.clone().into_iter().map(|a| a.to_string()).collect::<Vec<_>>(),
+            vec!["NOx".to_string(), "O3".to_string()]
+        );
     }
 
     #[test]
     fn parse_application() {
-        println!("Application");
+        let raw = r#"
+        application:
+            type is Web
+            layout is Pwa
+            roles:
+                Admin
+            visualizations:
+                Readings:
+                    type is Table
+                    source is AirQuality
+                    data:
+                        NOx
+                        O3
+        "#;
+
+        let Ok((_rest, _line, application)) = Parser::parse_application(raw, 1) else {
+            panic!("Could not parse")
+        };
+
+        assert_eq!(application.r#type.to_string(), "Web");
+        assert_eq!(application.layout.to_string(), "Pwa");
+        assert!(matches!(
+            application.roles.clone().into_iter().next(),
+            Some(Roles::Admin)
+        ));
+
+        let vis = application
+            .visualizations
+            .get("Readings")
+            .expect("Readings visualization");
+        assert!(matches!(vis.r#type, VisType::Table));
+        assert_eq!(vis.source.to_string(), "AirQuality");
     }
 
     #[test]
     fn parse_deployment() {
-        println!("Deployment");
+        let raw = r#"
+        deployment:
+            env:
+                local:
+                    uri is http://localhost/test
+                    port is 50055
+                    type is Docker
+                    replicas is 3
+        "#;
+
+        let Ok((_rest, _line, deployment)) = Parser::parse_deployment(raw, 1) else {
+            panic!("Could not parse")
+        };
+
+        let env = deployment.env.get("local").expect("local environment");
+
+        assert!(matches!(env.r#type, DeploymentType::Docker));
+        assert_eq!(*env.port, 50055);
+        assert_eq!(*env.replicas, 3);
+    }
+
+    #[test]
+    fn parse_full_smart_service() {
+        let raw = r#"
+        service:
+            name is Air Quality Madrid
+            version is 1.0.0
+            scope is Environment
+        data_sources:
+            measurements:
+                AirQuality:
+                    provider is Fiware
+                    type is Sensor
+                    uri is http://fiware.local/v2
+                    query:
+                        type is AirQualityObserved
+                        select:
+                            NOx
+        application:
+            type is Web
+            layout is SinglePage
+            visualizations:
+                Readings:
+                    type is Table
+                    source is AirQuality
+                    data:
+                        NOx
+        deployment:
+            env:
+                local:
+                    uri is http://localhost/test
+                    port is 50055
+                    type is Docker
+        "#;
+
+        let service = Parser::new(raw).parse().expect("Could not parse");
+
+        assert_eq!(service.service.name.to_string(), "Air Quality Madrid");
+        assert!(service.data_sources.measurements.get("AirQuality").is_some());
+        assert!(service.application.visualizations.get("Readings").is_some());
+        assert!(service.deployment.env.get("local").is_some());
+    }
+
+    #[test]
+    fn error_reports_absolute_line_number_past_the_first_block() {
+        let raw = r#"
+        service:
+            name is Air Quality Madrid
+            version is 1.0.0
+            scope is Environment
+        data_sources:
+            measurements:
+                AirQuality:
+                    provider is Fiware
+                    type is Sensor
+                    uri is http://fiware.local/v2
+                    query:
+                        type is AirQualityObserved
+                        select:
+                            NOx
+        application:
+            type is Web
+            layout is SinglePage
+            visualizations:
+                Readings:
+                    type is Table
+                    source is AirQuality
+                    data:
+                        NOx
+        deployment:
+            env:
+                local:
+                    uri is http://localhost/test
+                    port is 50055
+                    type is Docker
+                    bogus is field
+        "#;
+
+        let Err(error) = Parser::new(raw).parse() else {
+            panic!("expected the bogus field to be rejected")
+        };
+
+        assert!(
+            error.to_string().contains("line 31"),
+            "expected the error to cite line 31, got: {error}"
+        );
     }
 }